@@ -0,0 +1,129 @@
+//! Events emitted by token-related transactions: transparent transfers,
+//! allowance management and delegated transfers.
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+use namada_core::address::Address;
+use namada_events::extend::UserAccount;
+use namada_events::EventLevel;
+
+use crate::DenominatedAmount;
+
+/// An event describing a token operation that took place in a transaction.
+#[derive(Debug, Clone)]
+pub struct TokenEvent {
+    /// Human-readable description of where the event originated.
+    pub descriptor: Cow<'static, str>,
+    /// The event's level.
+    pub level: EventLevel,
+    /// The operation the event describes.
+    pub operation: TokenOperation,
+}
+
+/// The kind of token operation carried by a [`TokenEvent`].
+#[derive(Debug, Clone)]
+pub enum TokenOperation {
+    /// A single transparent transfer between two accounts.
+    SingleTransfer {
+        source: UserAccount,
+        target: UserAccount,
+        token: Address,
+        amount: DenominatedAmount,
+        source_post_balance: DenominatedAmount,
+        target_post_balance: Option<DenominatedAmount>,
+        /// Opaque caller-supplied context, e.g. an invoice ID.
+        memo: Option<Vec<u8>>,
+    },
+    /// A batched transparent transfer across many sources and targets.
+    Transfer {
+        sources: BTreeMap<(UserAccount, Address), DenominatedAmount>,
+        targets: BTreeMap<(UserAccount, Address), DenominatedAmount>,
+        post_balances: BTreeMap<(UserAccount, Address), DenominatedAmount>,
+        /// Opaque caller-supplied context, e.g. an invoice ID.
+        memo: Option<Vec<u8>>,
+    },
+    /// `owner` authorized `spender` to move up to some amount of `token` on
+    /// their behalf.
+    Approval {
+        owner: UserAccount,
+        spender: UserAccount,
+        token: Address,
+        post_allowance: DenominatedAmount,
+    },
+    /// A transfer executed by a spender against an allowance previously
+    /// granted by the owner.
+    DelegatedTransfer {
+        spender: UserAccount,
+        owner: UserAccount,
+        target: UserAccount,
+        token: Address,
+        amount: DenominatedAmount,
+        owner_post_balance: DenominatedAmount,
+        target_post_balance: Option<DenominatedAmount>,
+        post_allowance: DenominatedAmount,
+    },
+}
+
+impl TokenOperation {
+    /// Build a [`TokenOperation::SingleTransfer`].
+    #[allow(clippy::too_many_arguments)]
+    pub fn transfer(
+        source: UserAccount,
+        target: UserAccount,
+        token: Address,
+        amount: DenominatedAmount,
+        source_post_balance: DenominatedAmount,
+        target_post_balance: Option<DenominatedAmount>,
+        memo: Option<Vec<u8>>,
+    ) -> Self {
+        Self::SingleTransfer {
+            source,
+            target,
+            token,
+            amount,
+            source_post_balance,
+            target_post_balance,
+            memo,
+        }
+    }
+
+    /// Build a [`TokenOperation::Approval`].
+    pub fn approval(
+        owner: UserAccount,
+        spender: UserAccount,
+        token: Address,
+        post_allowance: DenominatedAmount,
+    ) -> Self {
+        Self::Approval {
+            owner,
+            spender,
+            token,
+            post_allowance,
+        }
+    }
+
+    /// Build a [`TokenOperation::DelegatedTransfer`].
+    #[allow(clippy::too_many_arguments)]
+    pub fn delegated_transfer(
+        spender: UserAccount,
+        owner: UserAccount,
+        target: UserAccount,
+        token: Address,
+        amount: DenominatedAmount,
+        owner_post_balance: DenominatedAmount,
+        target_post_balance: Option<DenominatedAmount>,
+        post_allowance: DenominatedAmount,
+    ) -> Self {
+        Self::DelegatedTransfer {
+            spender,
+            owner,
+            target,
+            token,
+            amount,
+            owner_post_balance,
+            target_post_balance,
+            post_allowance,
+        }
+    }
+}
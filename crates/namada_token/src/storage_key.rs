@@ -0,0 +1,23 @@
+//! Storage key layouts for token-related state. This module defines the key
+//! for allowances. Keys live under the relevant token's own subspace so that the
+//! `Multitoken` internal address can validate access to them uniformly.
+
+use namada_core::address::Address;
+use namada_core::storage::{Key, KeySeg};
+
+/// The storage key segment under which an owner's allowance for a given
+/// spender and token is recorded.
+const ALLOWANCE_STORAGE_KEY: &str = "allowance";
+
+/// The storage key under which `owner` records how much of `token` they
+/// have authorized `spender` to move out of their balance on their behalf,
+/// e.g. via a `transfer_from`-style delegated transfer.
+pub fn allowance_key(owner: &Address, spender: &Address, token: &Address) -> Key {
+    Key::from(token.to_db_key())
+        .push(&ALLOWANCE_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+        .push(&owner.to_db_key())
+        .expect("Cannot obtain a storage key")
+        .push(&spender.to_db_key())
+        .expect("Cannot obtain a storage key")
+}